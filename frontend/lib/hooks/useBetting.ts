@@ -1,7 +1,7 @@
 import { useState } from "react";
 import { extractAndVerifySpell } from "charms-js";
 import { BetData, MatchData, MatchResult } from "../types";
-import { calculatePayout } from "../utils";
+import { calculatePayout, LAUNCH_HOUSE_EDGE_BPS } from "../utils";
 import { fetchTransactionHex } from "./useCharms";
 
 export function useBetting() {
@@ -115,9 +115,10 @@ export function useBetting() {
   const calculatePotentialWin = (
     stake: number,
     odds: number,
-    hasBadge: boolean
+    hasBadge: boolean,
+    houseEdgeBps: number = LAUNCH_HOUSE_EDGE_BPS
   ) => {
-    return calculatePayout(stake, odds, hasBadge);
+    return calculatePayout(stake, odds, houseEdgeBps, hasBadge);
   };
 
   // Fetch user's existing bets from Bitcoin transactions
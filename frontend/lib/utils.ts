@@ -13,25 +13,22 @@ export function formatOdds(oddsBps: number): string {
   return (oddsBps / 10000).toFixed(2) + "x";
 }
 
+// Rate charged by houses that have not changed their fees since launch (4%)
+export const LAUNCH_HOUSE_EDGE_BPS = 400;
+
+// Mirrors the contract's rounding: the return at the odds and the badge bonus on it are
+// rounded down, the house edge on both is rounded up
 export function calculatePayout(
   stake: number,
   odds: number,
+  houseEdgeBps: number,
   hasBadge: boolean = false,
   bonusBps: number = 500
 ): number {
-  let finalOdds = odds;
-
-  // Apply badge bonus
-  if (hasBadge) {
-    finalOdds = finalOdds + Math.floor((finalOdds * bonusBps) / 10000);
-  }
-
-  // Apply house edge (4%)
-  const HOUSE_EDGE_BPS = 400;
-  finalOdds = finalOdds - Math.floor((finalOdds * HOUSE_EDGE_BPS) / 10000);
-
-  // Calculate payout
-  return Math.floor((stake * finalOdds) / 10000);
+  const gross = Math.floor((stake * odds) / 10000);
+  const badgeUplift = hasBadge ? Math.floor((gross * bonusBps) / 10000) : 0;
+  const houseEdge = Math.ceil(((gross + badgeUplift) * houseEdgeBps) / 10000);
+  return gross + badgeUplift - houseEdge;
 }
 
 export function generateMatchResult(